@@ -0,0 +1,400 @@
+use crate::error::MetricsNotifierError;
+use chrono::{DateTime, Duration, Utc};
+use rusoto_cloudwatch::Datapoint;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Condition {
+    Above,
+    Below,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SegmentKind {
+    Threshold(Condition),
+    Anomaly,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Segment {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub kind: SegmentKind,
+}
+
+pub trait AnalyticUnit {
+    fn detect(&self, points: &[Datapoint]) -> Result<Vec<Segment>, MetricsNotifierError>;
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Statistic {
+    Average,
+    Maximum,
+    Minimum,
+    Percentile(String),
+}
+
+pub struct ThresholdUnit {
+    pub value: f64,
+    pub condition: Condition,
+    pub statistic: Statistic,
+}
+
+impl ThresholdUnit {
+    fn crosses(&self, value: f64) -> bool {
+        match self.condition {
+            Condition::Above => value > self.value,
+            Condition::Below => value < self.value,
+        }
+    }
+
+    fn statistic_value(&self, point: &Datapoint) -> Result<f64, MetricsNotifierError> {
+        match &self.statistic {
+            Statistic::Average => point.average.ok_or(MetricsNotifierError::NoneValue),
+            Statistic::Maximum => point.maximum.ok_or(MetricsNotifierError::NoneValue),
+            Statistic::Minimum => point.minimum.ok_or(MetricsNotifierError::NoneValue),
+            Statistic::Percentile(label) => point
+                .extended_statistics
+                .as_ref()
+                .ok_or(MetricsNotifierError::NoneValue)?
+                .get(label)
+                .copied()
+                .ok_or(MetricsNotifierError::NoneValue),
+        }
+    }
+}
+
+impl AnalyticUnit for ThresholdUnit {
+    fn detect(&self, points: &[Datapoint]) -> Result<Vec<Segment>, MetricsNotifierError> {
+        if points.len() < 2 {
+            return Ok(vec![]);
+        }
+
+        let mut segments = Vec::new();
+        let mut run_start: Option<DateTime<Utc>> = None;
+        let mut run_end: Option<DateTime<Utc>> = None;
+
+        for point in points {
+            let value = self.statistic_value(point)?;
+            let timestamp = timestamp_of(point)?;
+
+            if self.crosses(value) {
+                if run_start.is_none() {
+                    run_start = Some(timestamp);
+                }
+                run_end = Some(timestamp);
+            } else if let (Some(from), Some(to)) = (run_start.take(), run_end.take()) {
+                segments.push(Segment {
+                    from,
+                    to,
+                    kind: SegmentKind::Threshold(self.condition),
+                });
+            }
+        }
+
+        if let (Some(from), Some(to)) = (run_start, run_end) {
+            segments.push(Segment {
+                from,
+                to,
+                kind: SegmentKind::Threshold(self.condition),
+            });
+        }
+
+        Ok(segments)
+    }
+}
+
+pub struct AnomalyUnit {
+    pub confidence: f64,
+    pub seasonality: Option<Duration>,
+}
+
+impl AnalyticUnit for AnomalyUnit {
+    fn detect(&self, points: &[Datapoint]) -> Result<Vec<Segment>, MetricsNotifierError> {
+        if points.len() < 2 {
+            return Ok(vec![]);
+        }
+
+        let averages = points
+            .iter()
+            .map(|point| point.average.ok_or(MetricsNotifierError::NoneValue))
+            .collect::<Result<Vec<f64>, MetricsNotifierError>>()?;
+        let timestamps = points
+            .iter()
+            .map(timestamp_of)
+            .collect::<Result<Vec<DateTime<Utc>>, MetricsNotifierError>>()?;
+
+        let buckets = self.bucket_indices(&timestamps);
+
+        let mut is_anomalous = vec![false; points.len()];
+        for bucket in buckets {
+            let (mean, std_dev) = mean_and_std_dev(bucket.iter().map(|&i| averages[i]));
+            if std_dev == 0.0 {
+                continue;
+            }
+            for &i in &bucket {
+                if (averages[i] - mean).abs() > self.confidence * std_dev {
+                    is_anomalous[i] = true;
+                }
+            }
+        }
+
+        let mut segments = Vec::new();
+        let mut run_start: Option<DateTime<Utc>> = None;
+        let mut run_end: Option<DateTime<Utc>> = None;
+        for (i, anomalous) in is_anomalous.into_iter().enumerate() {
+            if anomalous {
+                if run_start.is_none() {
+                    run_start = Some(timestamps[i]);
+                }
+                run_end = Some(timestamps[i]);
+            } else if let (Some(from), Some(to)) = (run_start.take(), run_end.take()) {
+                segments.push(Segment {
+                    from,
+                    to,
+                    kind: SegmentKind::Anomaly,
+                });
+            }
+        }
+        if let (Some(from), Some(to)) = (run_start, run_end) {
+            segments.push(Segment {
+                from,
+                to,
+                kind: SegmentKind::Anomaly,
+            });
+        }
+
+        Ok(segments)
+    }
+}
+
+impl AnomalyUnit {
+    fn bucket_indices(&self, timestamps: &[DateTime<Utc>]) -> Vec<Vec<usize>> {
+        let seasonality = match self.seasonality {
+            Some(seasonality) if seasonality.num_seconds() > 0 => seasonality,
+            _ => return vec![(0..timestamps.len()).collect()],
+        };
+
+        let epoch = timestamps[0];
+        let mut buckets: std::collections::HashMap<i64, Vec<usize>> = std::collections::HashMap::new();
+        for (i, timestamp) in timestamps.iter().enumerate() {
+            let offset = (*timestamp - epoch).num_seconds().rem_euclid(seasonality.num_seconds());
+            buckets.entry(offset).or_default().push(i);
+        }
+        buckets.into_values().collect()
+    }
+}
+
+fn timestamp_of(point: &Datapoint) -> Result<DateTime<Utc>, MetricsNotifierError> {
+    point
+        .timestamp
+        .as_ref()
+        .ok_or(MetricsNotifierError::NoneValue)?
+        .parse::<DateTime<Utc>>()
+        .map_err(|_| MetricsNotifierError::NoneValue)
+}
+
+fn mean_and_std_dev(values: impl Iterator<Item = f64> + Clone) -> (f64, f64) {
+    let count = values.clone().count() as f64;
+    let mean = values.clone().sum::<f64>() / count;
+    let variance = values.map(|value| (value - mean).powi(2)).sum::<f64>() / count;
+    (mean, variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datapoint(average: f64, timestamp: &str) -> Datapoint {
+        Datapoint {
+            average: Some(average),
+            maximum: None,
+            minimum: None,
+            extended_statistics: None,
+            sample_count: None,
+            sum: None,
+            timestamp: Some(timestamp.to_string()),
+            unit: None,
+        }
+    }
+
+    #[test]
+    fn test_threshold_detects_contiguous_run_against_percentile() {
+        let mut breaching = std::collections::HashMap::new();
+        breaching.insert("p99".to_string(), 95.0);
+        let mut quiet = std::collections::HashMap::new();
+        quiet.insert("p99".to_string(), 10.0);
+
+        let unit = ThresholdUnit {
+            value: 80.0,
+            condition: Condition::Above,
+            statistic: Statistic::Percentile("p99".to_string()),
+        };
+        let points = vec![
+            Datapoint {
+                average: Some(5.0),
+                maximum: None,
+                minimum: None,
+                extended_statistics: Some(quiet),
+                sample_count: None,
+                sum: None,
+                timestamp: Some("2020-12-01T00:00:00Z".to_string()),
+                unit: None,
+            },
+            Datapoint {
+                average: Some(5.0),
+                maximum: None,
+                minimum: None,
+                extended_statistics: Some(breaching),
+                sample_count: None,
+                sum: None,
+                timestamp: Some("2020-12-01T00:05:00Z".to_string()),
+                unit: None,
+            },
+        ];
+
+        let segments = unit.detect(&points).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].kind, SegmentKind::Threshold(Condition::Above));
+    }
+
+    #[test]
+    fn test_threshold_detects_contiguous_run() {
+        let unit = ThresholdUnit {
+            value: 80.0,
+            condition: Condition::Above,
+            statistic: Statistic::Average,
+        };
+        let points = vec![
+            datapoint(10.0, "2020-12-01T00:00:00Z"),
+            datapoint(90.0, "2020-12-01T00:05:00Z"),
+            datapoint(95.0, "2020-12-01T00:10:00Z"),
+            datapoint(20.0, "2020-12-01T00:15:00Z"),
+        ];
+
+        let segments = unit.detect(&points).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].kind, SegmentKind::Threshold(Condition::Above));
+    }
+
+    #[test]
+    fn test_threshold_too_few_points() {
+        let unit = ThresholdUnit {
+            value: 80.0,
+            condition: Condition::Above,
+            statistic: Statistic::Average,
+        };
+        let segments = unit.detect(&[datapoint(90.0, "2020-12-01T00:00:00Z")]).unwrap();
+        assert!(segments.is_empty());
+    }
+
+    #[test]
+    fn test_threshold_missing_average_is_error() {
+        let unit = ThresholdUnit {
+            value: 80.0,
+            condition: Condition::Above,
+            statistic: Statistic::Average,
+        };
+        let points = vec![
+            datapoint(10.0, "2020-12-01T00:00:00Z"),
+            Datapoint {
+                average: None,
+                maximum: None,
+                minimum: None,
+                extended_statistics: None,
+                sample_count: None,
+                sum: None,
+                timestamp: Some("2020-12-01T00:05:00Z".to_string()),
+                unit: None,
+            },
+        ];
+        assert_eq!(
+            unit.detect(&points).err().unwrap(),
+            MetricsNotifierError::NoneValue
+        );
+    }
+
+    #[test]
+    fn test_anomaly_flags_outlier() {
+        let unit = AnomalyUnit {
+            confidence: 2.0,
+            seasonality: None,
+        };
+        let points = vec![
+            datapoint(50.0, "2020-12-01T00:00:00Z"),
+            datapoint(51.0, "2020-12-01T00:05:00Z"),
+            datapoint(49.0, "2020-12-01T00:10:00Z"),
+            datapoint(150.0, "2020-12-01T00:15:00Z"),
+            datapoint(50.0, "2020-12-01T00:20:00Z"),
+            datapoint(50.0, "2020-12-01T00:25:00Z"),
+        ];
+
+        let segments = unit.detect(&points).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].kind, SegmentKind::Anomaly);
+    }
+
+    #[test]
+    fn test_anomaly_zero_std_dev_is_never_anomalous() {
+        let unit = AnomalyUnit {
+            confidence: 1.0,
+            seasonality: None,
+        };
+        let points = vec![
+            datapoint(50.0, "2020-12-01T00:00:00Z"),
+            datapoint(50.0, "2020-12-01T00:05:00Z"),
+            datapoint(50.0, "2020-12-01T00:10:00Z"),
+        ];
+        assert!(unit.detect(&points).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bucket_indices_groups_same_time_of_day() {
+        let unit = AnomalyUnit {
+            confidence: 1.0,
+            seasonality: Some(Duration::days(1)),
+        };
+        let timestamps = [
+            "2020-12-01T00:00:00Z",
+            "2020-12-01T12:00:00Z",
+            "2020-12-02T00:00:00Z",
+            "2020-12-02T12:00:00Z",
+        ]
+        .iter()
+        .map(|timestamp| timestamp.parse::<DateTime<Utc>>().unwrap())
+        .collect::<Vec<_>>();
+
+        let mut buckets = unit.bucket_indices(&timestamps);
+        buckets.sort_by_key(|bucket| bucket[0]);
+
+        assert_eq!(buckets, vec![vec![0, 2], vec![1, 3]]);
+    }
+
+    #[test]
+    fn test_anomaly_with_seasonality_ignores_different_time_of_day_baseline() {
+        // Three "00:00" points at 10 and four "12:00" points at 200: each time-of-day
+        // bucket is perfectly steady on its own, but the two baselines differ enough
+        // that comparing everything as one series would make the low bucket look
+        // anomalous relative to the high bucket's pull on the global mean.
+        let points = vec![
+            datapoint(10.0, "2020-12-01T00:00:00Z"),
+            datapoint(200.0, "2020-12-01T12:00:00Z"),
+            datapoint(10.0, "2020-12-02T00:00:00Z"),
+            datapoint(200.0, "2020-12-02T12:00:00Z"),
+            datapoint(10.0, "2020-12-03T00:00:00Z"),
+            datapoint(200.0, "2020-12-03T12:00:00Z"),
+            datapoint(200.0, "2020-12-04T12:00:00Z"),
+        ];
+
+        let without_seasonality = AnomalyUnit {
+            confidence: 1.0,
+            seasonality: None,
+        };
+        assert!(!without_seasonality.detect(&points).unwrap().is_empty());
+
+        let with_seasonality = AnomalyUnit {
+            confidence: 1.0,
+            seasonality: Some(Duration::days(1)),
+        };
+        assert!(with_seasonality.detect(&points).unwrap().is_empty());
+    }
+}
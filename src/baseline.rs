@@ -0,0 +1,161 @@
+use crate::error::MetricsNotifierError;
+use crate::metric::AggregatedMetrics;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BaselineRecord {
+    pub metric_name: String,
+    pub instance_id: String,
+    pub recorded_at: DateTime<Utc>,
+    pub average: f64,
+    pub maximum: f64,
+    pub minimum: f64,
+}
+
+impl BaselineRecord {
+    pub fn new(metric_name: &str, instance_id: &str, recorded_at: DateTime<Utc>, metrics: &AggregatedMetrics) -> Self {
+        BaselineRecord {
+            metric_name: metric_name.to_string(),
+            instance_id: instance_id.to_string(),
+            recorded_at,
+            average: metrics.average,
+            maximum: metrics.maximum,
+            minimum: metrics.minimum,
+        }
+    }
+
+    pub fn metrics(&self) -> AggregatedMetrics {
+        AggregatedMetrics {
+            average: self.average,
+            maximum: self.maximum,
+            minimum: self.minimum,
+            ..Default::default()
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MetricsNotifierError> {
+        let contents = serde_json::to_string(self)
+            .map_err(|error| MetricsNotifierError::SerializationError(error.to_string()))?;
+        fs::write(path, contents).map_err(|error| MetricsNotifierError::UploadError(error.to_string()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MetricsNotifierError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|error| MetricsNotifierError::UploadError(error.to_string()))?;
+        serde_json::from_str(&contents)
+            .map_err(|error| MetricsNotifierError::SerializationError(error.to_string()))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum FieldChange {
+    Change { absolute: f64, percentage: f64 },
+    New,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetricsDelta {
+    pub average: FieldChange,
+    pub maximum: FieldChange,
+    pub minimum: FieldChange,
+}
+
+impl MetricsDelta {
+    pub fn exceeds_tolerance(&self, tolerance: f64) -> bool {
+        [&self.average, &self.maximum, &self.minimum]
+            .iter()
+            .any(|change| match change {
+                FieldChange::Change { percentage, .. } => percentage.abs() > tolerance,
+                FieldChange::New => true,
+            })
+    }
+}
+
+pub trait CompareToBaseline {
+    fn compare_to_baseline(&self, baseline: &AggregatedMetrics) -> MetricsDelta;
+}
+
+impl CompareToBaseline for AggregatedMetrics {
+    fn compare_to_baseline(&self, baseline: &AggregatedMetrics) -> MetricsDelta {
+        MetricsDelta {
+            average: field_change(self.average, baseline.average),
+            maximum: field_change(self.maximum, baseline.maximum),
+            minimum: field_change(self.minimum, baseline.minimum),
+        }
+    }
+}
+
+fn field_change(current: f64, base: f64) -> FieldChange {
+    if base == 0.0 {
+        return FieldChange::New;
+    }
+    let absolute = current - base;
+    let percentage = absolute / base * 100.0;
+    FieldChange::Change { absolute, percentage }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn metrics(average: f64, maximum: f64, minimum: f64) -> AggregatedMetrics {
+        AggregatedMetrics {
+            average,
+            maximum,
+            minimum,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compare_to_baseline_reports_percentage_change() {
+        let current = metrics(55.0, 90.0, 10.0);
+        let baseline = metrics(50.0, 90.0, 10.0);
+
+        let delta = current.compare_to_baseline(&baseline);
+        assert_eq!(
+            delta.average,
+            FieldChange::Change { absolute: 5.0, percentage: 10.0 }
+        );
+        assert_eq!(
+            delta.maximum,
+            FieldChange::Change { absolute: 0.0, percentage: 0.0 }
+        );
+    }
+
+    #[test]
+    fn test_compare_to_baseline_zero_base_is_new() {
+        let current = metrics(55.0, 90.0, 10.0);
+        let baseline = metrics(0.0, 90.0, 10.0);
+
+        let delta = current.compare_to_baseline(&baseline);
+        assert_eq!(delta.average, FieldChange::New);
+    }
+
+    #[test]
+    fn test_exceeds_tolerance() {
+        let current = metrics(55.0, 90.0, 10.0);
+        let baseline = metrics(50.0, 90.0, 10.0);
+        let delta = current.compare_to_baseline(&baseline);
+
+        assert!(delta.exceeds_tolerance(5.0));
+        assert!(!delta.exceeds_tolerance(20.0));
+    }
+
+    #[test]
+    fn test_baseline_record_round_trips_through_disk() {
+        let recorded_at = DateTime::<Utc>::from_str("2020-12-01T15:00:00.0+00:00").unwrap();
+        let record = BaselineRecord::new("CPUUtilization", "i-1234567890abcdef0", recorded_at, &metrics(55.0, 90.0, 10.0));
+
+        let path = std::env::temp_dir().join(format!("metrics-notifier-baseline-test-{}.json", std::process::id()));
+        record.save(&path).unwrap();
+        let loaded = BaselineRecord::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded, record);
+    }
+}
@@ -2,11 +2,14 @@ use crate::error::MetricsNotifierError;
 use async_trait::async_trait;
 
 use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
-use rusoto_cloudwatch::{CloudWatch, CloudWatchClient, Datapoint, GetMetricStatisticsInput};
+use rusoto_cloudwatch::{CloudWatch, CloudWatchClient, Datapoint, Dimension, GetMetricStatisticsInput};
 
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::ops::{Add, Div};
-use crate::metric::AggregatedMetrics;
+use crate::ec2_instance_client::MachineInstance;
+use crate::metric::{AggregatedMetrics, MetricQuery};
+use crate::retry::with_backoff;
 use crate::time_range::TimeRange;
 
 const DEFAULT_STATISTICS: [&'static str; 3] = ["Average", "Minimum", "Maximum"];
@@ -17,41 +20,94 @@ pub struct CloudWatchMetricsClient {
 
 #[async_trait]
 pub trait Aggregate {
-    async fn aggregate_metrics(&self, time_range: &TimeRange) -> Result<AggregatedMetrics, MetricsNotifierError>;
+    async fn fetch_datapoints(&self, query: &MetricQuery, time_range: &TimeRange) -> Result<Vec<Datapoint>, MetricsNotifierError>;
+
+    async fn aggregate_metrics(&self, query: &MetricQuery, time_range: &TimeRange) -> Result<AggregatedMetrics, MetricsNotifierError>;
+
+    async fn aggregate_metrics_for_instances(
+        &self,
+        instances: &[MachineInstance],
+        query: &MetricQuery,
+        time_range: &TimeRange,
+    ) -> Result<HashMap<String, AggregatedMetrics>, MetricsNotifierError>;
 }
 
 #[async_trait]
 impl Aggregate for CloudWatchMetricsClient {
-    async fn aggregate_metrics(&self, time_range: &TimeRange) -> Result<AggregatedMetrics, MetricsNotifierError> {
-        let metrics = self
-            .client
-            .get_metric_statistics(GetMetricStatisticsInput {
-                start_time: time_range.start.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-                end_time: time_range.end.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-                metric_name: "CPUUtilization".to_string(),
-                namespace: "AWS/EC2".to_string(),
-                period: 0,
-                statistics: Some(
-                    DEFAULT_STATISTICS
-                        .iter()
-                        .map(|statistic| statistic.to_string())
-                        .collect(),
-                ),
-                ..Default::default()
-            })
-            .await?;
-        self.aggregate_data_points(metrics.datapoints)
+    async fn fetch_datapoints(&self, query: &MetricQuery, time_range: &TimeRange) -> Result<Vec<Datapoint>, MetricsNotifierError> {
+        let metrics = with_backoff(|| async {
+            self.client
+                .get_metric_statistics(GetMetricStatisticsInput {
+                    start_time: time_range.start.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                    end_time: time_range.end.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                    metric_name: query.metric_name.clone(),
+                    namespace: query.namespace.clone(),
+                    period: query.period,
+                    dimensions: Some(
+                        query
+                            .dimensions
+                            .iter()
+                            .map(|(name, value)| Dimension {
+                                name: name.clone(),
+                                value: value.clone(),
+                            })
+                            .collect(),
+                    ),
+                    statistics: Some(
+                        DEFAULT_STATISTICS
+                            .iter()
+                            .map(|statistic| statistic.to_string())
+                            .collect(),
+                    ),
+                    extended_statistics: if query.extended_statistics.is_empty() {
+                        None
+                    } else {
+                        Some(query.extended_statistics.clone())
+                    },
+                    ..Default::default()
+                })
+                .await
+                .map_err(MetricsNotifierError::from)
+        })
+        .await?;
+        Ok(metrics.datapoints.unwrap_or_default())
+    }
+
+    async fn aggregate_metrics(&self, query: &MetricQuery, time_range: &TimeRange) -> Result<AggregatedMetrics, MetricsNotifierError> {
+        let datapoints = self.fetch_datapoints(query, time_range).await?;
+        self.aggregate_data_points(Some(datapoints), &query.extended_statistics)
+    }
+
+    async fn aggregate_metrics_for_instances(
+        &self,
+        instances: &[MachineInstance],
+        query: &MetricQuery,
+        time_range: &TimeRange,
+    ) -> Result<HashMap<String, AggregatedMetrics>, MetricsNotifierError> {
+        let mut aggregated = HashMap::with_capacity(instances.len());
+        for instance in instances {
+            let mut dimensions = query.dimensions.clone();
+            dimensions.push(("InstanceId".to_string(), instance.instance_id.clone()));
+            let per_instance_query = MetricQuery {
+                dimensions,
+                ..query.clone()
+            };
+            let metrics = self.aggregate_metrics(&per_instance_query, time_range).await?;
+            aggregated.insert(instance.instance_id.clone(), metrics);
+        }
+        Ok(aggregated)
     }
 }
 
 impl CloudWatchMetricsClient {
-    fn new_with_client(client: CloudWatchClient) -> Self {
+    pub fn new_with_client(client: CloudWatchClient) -> Self {
         CloudWatchMetricsClient { client }
     }
 
     fn aggregate_data_points(
         &self,
         data_points: Option<Vec<Datapoint>>,
+        percentiles: &[String],
     ) -> Result<AggregatedMetrics, MetricsNotifierError> {
         let data_points = data_points.map_or(vec![], |points| points);
         if data_points.is_empty() {
@@ -62,7 +118,9 @@ impl CloudWatchMetricsClient {
         let mut maximum = 0.0f64;
         let length = u32::try_from(data_points.len())?;
         let count = BigDecimal::from(length);
-        for data_point in data_points {
+        let mut weighted_sums: HashMap<&String, f64> = HashMap::new();
+        let mut weights: HashMap<&String, f64> = HashMap::new();
+        for data_point in &data_points {
             let average = data_point
                 .average
                 .map(|average| {
@@ -73,16 +131,40 @@ impl CloudWatchMetricsClient {
 
             minimum = minimum.min(data_point.minimum.ok_or(MetricsNotifierError::NoneValue)?);
             maximum = maximum.max(data_point.maximum.ok_or(MetricsNotifierError::NoneValue)?);
+
+            if !percentiles.is_empty() {
+                let sample_count = data_point.sample_count.ok_or(MetricsNotifierError::NoneValue)?;
+                let extended_statistics = data_point
+                    .extended_statistics
+                    .as_ref()
+                    .ok_or(MetricsNotifierError::NoneValue)?;
+                for percentile in percentiles {
+                    let value = extended_statistics
+                        .get(percentile)
+                        .ok_or(MetricsNotifierError::NoneValue)?;
+                    *weighted_sums.entry(percentile).or_insert(0.0) += value * sample_count;
+                    *weights.entry(percentile).or_insert(0.0) += sample_count;
+                }
+            }
         }
 
         let decimal_average = total.div(count);
         let average = decimal_average
             .to_f64()
             .ok_or(MetricsNotifierError::ToPrimitive)?;
+
+        let mut percentile_values = BTreeMap::new();
+        for percentile in percentiles {
+            let sum = weighted_sums.get(percentile).copied().unwrap_or(0.0);
+            let weight = weights.get(percentile).copied().unwrap_or(0.0);
+            percentile_values.insert(percentile.clone(), sum / weight);
+        }
+
         Ok(AggregatedMetrics {
             average,
             maximum,
             minimum,
+            percentiles: percentile_values,
         })
     }
 }
@@ -90,7 +172,9 @@ impl CloudWatchMetricsClient {
 #[cfg(test)]
 mod tests {
     use crate::cloud_watch_metrics_client::{Aggregate, AggregatedMetrics, CloudWatchMetricsClient};
+    use crate::ec2_instance_client::MachineInstance;
     use crate::error::MetricsNotifierError;
+    use crate::metric::MetricQuery;
     use rusoto_cloudwatch::{CloudWatchClient, Datapoint};
     use rusoto_core::Region;
     use rusoto_mock::{
@@ -98,9 +182,19 @@ mod tests {
     };
     use crate::time_range::TimeRange;
     use chrono::{DateTime, Utc};
+    use std::collections::HashMap;
     use std::str::FromStr;
     use std::convert::TryFrom;
 
+    fn cpu_utilization_query() -> MetricQuery {
+        MetricQuery {
+            namespace: "AWS/EC2".to_string(),
+            metric_name: "CPUUtilization".to_string(),
+            dimensions: vec![],
+            period: 0,
+            extended_statistics: vec![],
+        }
+    }
 
     #[tokio::test]
     async fn test_aggregate_metrics() {
@@ -116,7 +210,7 @@ mod tests {
         let beginning_of_month = DateTime::<Utc>::from_str("2019-01-12T00:00:00.0+00:00").unwrap();
         let range = TimeRange::try_from(beginning_of_month).unwrap();
         let client = CloudWatchMetricsClient::new_with_client(mock);
-        let result = client.aggregate_metrics(&range).await;
+        let result = client.aggregate_metrics(&cpu_utilization_query(), &range).await;
 
         assert_eq!(
             result.unwrap(),
@@ -124,6 +218,40 @@ mod tests {
                 average: 51.8,
                 maximum: 99.0,
                 minimum: 10.0,
+                percentiles: Default::default(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_metrics_for_instances() {
+        let mock = CloudWatchClient::new_with(
+            MockRequestDispatcher::default().with_body(&*MockResponseReader::read_response(
+                "test_resources/valid",
+                "get_metrics_data.xml",
+            )),
+            MockCredentialsProvider,
+            Default::default(),
+        );
+
+        let beginning_of_month = DateTime::<Utc>::from_str("2019-01-12T00:00:00.0+00:00").unwrap();
+        let range = TimeRange::try_from(beginning_of_month).unwrap();
+        let client = CloudWatchMetricsClient::new_with_client(mock);
+        let instances = vec![MachineInstance {
+            instance_id: "i-1234567890abcdef0".to_string(),
+        }];
+        let result = client
+            .aggregate_metrics_for_instances(&instances, &cpu_utilization_query(), &range)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.get("i-1234567890abcdef0").unwrap(),
+            &AggregatedMetrics {
+                average: 51.8,
+                maximum: 99.0,
+                minimum: 10.0,
+                percentiles: Default::default(),
             }
         );
     }
@@ -142,7 +270,7 @@ mod tests {
         let beginning_of_month = DateTime::<Utc>::from_str("2019-01-12T00:00:00.0+00:00").unwrap();
         let range = TimeRange::try_from(beginning_of_month).unwrap();
         let client = CloudWatchMetricsClient::new_with_client(mock);
-        let result = client.aggregate_metrics(&range).await;
+        let result = client.aggregate_metrics(&cpu_utilization_query(), &range).await;
 
         assert!(result.is_err());
     }
@@ -150,67 +278,129 @@ mod tests {
     #[tokio::test]
     async fn test_aggregate_data_points() {
         let client = CloudWatchMetricsClient::new_with_client(CloudWatchClient::new(Region::ApNortheast3));
-        let result = client.aggregate_data_points(Some(vec![
-            Datapoint {
-                average: Some(55.5),
-                maximum: Some(91.0),
-                minimum: Some(11.0),
-                extended_statistics: None,
-                sample_count: None,
-                sum: None,
-                timestamp: None,
-                unit: None,
-            },
-            Datapoint {
-                average: Some(28.8),
-                maximum: Some(92.0),
-                minimum: Some(13.0),
-                extended_statistics: None,
-                sample_count: None,
-                sum: None,
-                timestamp: None,
-                unit: None,
-            },
-            Datapoint {
-                average: Some(40.2),
-                maximum: Some(93.0),
-                minimum: Some(12.0),
-                extended_statistics: None,
-                sample_count: None,
-                sum: None,
-                timestamp: None,
-                unit: None,
-            },
-            Datapoint {
-                average: Some(51.3),
-                maximum: Some(93.0),
-                minimum: Some(12.0),
-                extended_statistics: None,
-                sample_count: None,
-                sum: None,
-                timestamp: None,
-                unit: None,
-            },
-        ]));
+        let result = client.aggregate_data_points(
+            Some(vec![
+                Datapoint {
+                    average: Some(55.5),
+                    maximum: Some(91.0),
+                    minimum: Some(11.0),
+                    extended_statistics: None,
+                    sample_count: None,
+                    sum: None,
+                    timestamp: None,
+                    unit: None,
+                },
+                Datapoint {
+                    average: Some(28.8),
+                    maximum: Some(92.0),
+                    minimum: Some(13.0),
+                    extended_statistics: None,
+                    sample_count: None,
+                    sum: None,
+                    timestamp: None,
+                    unit: None,
+                },
+                Datapoint {
+                    average: Some(40.2),
+                    maximum: Some(93.0),
+                    minimum: Some(12.0),
+                    extended_statistics: None,
+                    sample_count: None,
+                    sum: None,
+                    timestamp: None,
+                    unit: None,
+                },
+                Datapoint {
+                    average: Some(51.3),
+                    maximum: Some(93.0),
+                    minimum: Some(12.0),
+                    extended_statistics: None,
+                    sample_count: None,
+                    sum: None,
+                    timestamp: None,
+                    unit: None,
+                },
+            ]),
+            &[],
+        );
         assert_eq!(
             AggregatedMetrics {
                 average: 43.95,
                 maximum: 93.0,
                 minimum: 11.0,
+                percentiles: Default::default(),
             },
             result.unwrap()
         );
     }
 
+    #[tokio::test]
+    async fn test_aggregate_data_points_with_percentiles() {
+        let client = CloudWatchMetricsClient::new_with_client(CloudWatchClient::new(Region::ApNortheast3));
+        let mut first_extended = HashMap::new();
+        first_extended.insert("p90".to_string(), 80.0);
+        let mut second_extended = HashMap::new();
+        second_extended.insert("p90".to_string(), 90.0);
+
+        let result = client.aggregate_data_points(
+            Some(vec![
+                Datapoint {
+                    average: Some(50.0),
+                    maximum: Some(91.0),
+                    minimum: Some(11.0),
+                    extended_statistics: Some(first_extended),
+                    sample_count: Some(10.0),
+                    sum: None,
+                    timestamp: None,
+                    unit: None,
+                },
+                Datapoint {
+                    average: Some(50.0),
+                    maximum: Some(92.0),
+                    minimum: Some(13.0),
+                    extended_statistics: Some(second_extended),
+                    sample_count: Some(30.0),
+                    sum: None,
+                    timestamp: None,
+                    unit: None,
+                },
+            ]),
+            &["p90".to_string()],
+        );
+
+        let aggregated = result.unwrap();
+        assert_eq!(aggregated.percentiles.get("p90").unwrap(), &87.5);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_data_points_missing_percentile_is_error() {
+        let client = CloudWatchMetricsClient::new_with_client(CloudWatchClient::new(Region::ApNortheast3));
+        let result = client.aggregate_data_points(
+            Some(vec![Datapoint {
+                average: Some(50.0),
+                maximum: Some(91.0),
+                minimum: Some(11.0),
+                extended_statistics: Some(HashMap::new()),
+                sample_count: Some(10.0),
+                sum: None,
+                timestamp: None,
+                unit: None,
+            }]),
+            &["p90".to_string()],
+        );
+        assert_eq!(result.err().unwrap(), MetricsNotifierError::NoneValue);
+    }
+
     #[tokio::test]
     async fn test_aggregate_when_zero_value() {
         let client = CloudWatchMetricsClient::new_with_client(CloudWatchClient::new(Region::ApNortheast3));
-        let result = client.aggregate_data_points(Some(vec![]));
+        let result = client.aggregate_data_points(Some(vec![]), &[]);
         assert_eq!(
             AggregatedMetrics {
                 average: 0.0,
                 maximum: 0.0,
                 minimum: 0.0,
+                percentiles: Default::default(),
             },
             result.unwrap()
         );
@@ -219,16 +409,19 @@ mod tests {
     #[tokio::test]
     async fn test_dont_aggregate_when_no_value() {
         let client = CloudWatchMetricsClient::new_with_client(CloudWatchClient::new(Region::ApNortheast3));
-        let result = client.aggregate_data_points(Some(vec![Datapoint {
-            average: None,
-            maximum: None,
-            minimum: None,
-            extended_statistics: None,
-            sample_count: None,
-            sum: None,
-            timestamp: None,
-            unit: None,
-        }]));
+        let result = client.aggregate_data_points(
+            Some(vec![Datapoint {
+                average: None,
+                maximum: None,
+                minimum: None,
+                extended_statistics: None,
+                sample_count: None,
+                sum: None,
+                timestamp: None,
+                unit: None,
+            }]),
+            &[],
+        );
         assert_eq!(result.err().unwrap(), MetricsNotifierError::NoneValue)
     }
 }
@@ -1,7 +1,9 @@
 use std::error::Error;
 
 use rusoto_cloudwatch::GetMetricStatisticsError;
+use rusoto_core::request::BufferedHttpResponse;
 use rusoto_core::RusotoError;
+use serde::{Serialize, Serializer};
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 use std::num::TryFromIntError;
@@ -14,6 +16,9 @@ pub enum MetricsNotifierError {
     TryFromIntError,
     GetMetricsError(RusotoError<GetMetricStatisticsError>),
     DescribeInstancesError(RusotoError<DescribeInstancesError>),
+    SerializationError(String),
+    UploadError(String),
+    NotifyError(String),
 }
 
 impl Display for MetricsNotifierError {
@@ -26,10 +31,22 @@ impl Display for MetricsNotifierError {
             MetricsNotifierError::TryFromIntError => write!(f, "Failed to convert int"),
             MetricsNotifierError::GetMetricsError(ref error) => std::fmt::Display::fmt(error, f),
             MetricsNotifierError::DescribeInstancesError(ref error) => std::fmt::Display::fmt(error, f),
+            MetricsNotifierError::SerializationError(ref message) => write!(f, "Failed to serialize: {}", message),
+            MetricsNotifierError::UploadError(ref message) => write!(f, "Failed to upload report: {}", message),
+            MetricsNotifierError::NotifyError(ref message) => write!(f, "Failed to deliver notification: {}", message),
         }
     }
 }
 
+impl Serialize for MetricsNotifierError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 impl Error for MetricsNotifierError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match *self {
@@ -55,4 +72,43 @@ impl From<RusotoError<DescribeInstancesError>> for MetricsNotifierError {
     fn from(e: RusotoError<DescribeInstancesError>) -> MetricsNotifierError {
         MetricsNotifierError::DescribeInstancesError(e)
     }
+}
+
+impl MetricsNotifierError {
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            MetricsNotifierError::GetMetricsError(error) => is_rusoto_error_retryable(error),
+            MetricsNotifierError::DescribeInstancesError(error) => is_rusoto_error_retryable(error),
+            _ => false,
+        }
+    }
+
+    pub fn is_throttling(&self) -> bool {
+        match self {
+            MetricsNotifierError::GetMetricsError(error) => is_rusoto_error_throttling(error),
+            MetricsNotifierError::DescribeInstancesError(error) => is_rusoto_error_throttling(error),
+            _ => false,
+        }
+    }
+}
+
+fn is_rusoto_error_retryable<E>(error: &RusotoError<E>) -> bool {
+    match error {
+        RusotoError::HttpDispatch(_) => true,
+        RusotoError::Unknown(response) => response.status.is_server_error() || is_throttling_response(response),
+        _ => false,
+    }
+}
+
+fn is_rusoto_error_throttling<E>(error: &RusotoError<E>) -> bool {
+    match error {
+        RusotoError::Unknown(response) => is_throttling_response(response),
+        _ => false,
+    }
+}
+
+fn is_throttling_response(response: &BufferedHttpResponse) -> bool {
+    response.status.as_u16() == 429
+        || String::from_utf8_lossy(&response.body).contains("Throttling")
+        || String::from_utf8_lossy(&response.body).contains("RequestLimitExceeded")
 }
\ No newline at end of file
@@ -1,22 +1,44 @@
-mod client;
+mod analytic;
+mod baseline;
+mod cloud_watch_metrics_client;
+mod ec2_instance_client;
 mod error;
+mod metric;
+mod notificator;
+mod output;
+mod retry;
+mod sink;
+mod time_range;
+mod upload;
 
 use lambda::{handler_fn, Context};
-use rusoto_cloudwatch::{CloudWatch, CloudWatchClient, GetMetricStatisticsInput};
+use rusoto_cloudwatch::CloudWatchClient;
 use rusoto_core::Region;
 use rusoto_ec2::Ec2Client;
 
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::Value;
+use std::convert::TryFrom;
+use std::env;
+use std::path::Path;
+
+use analytic::{AnalyticUnit, AnomalyUnit, Condition, Statistic, ThresholdUnit};
+use baseline::{BaselineRecord, CompareToBaseline};
+use chrono::{Duration, Utc};
+use cloud_watch_metrics_client::{Aggregate, CloudWatchMetricsClient};
+use ec2_instance_client::{Describe, Ec2InstanceClient};
+use metric::MetricQuery;
+use notificator::{NoopNotificator, NotificationContext, Notificator, WebhookNotificator};
+use output::{write_to_stdout, MetricReport};
+use sink::{MetricsSink, StatsdSink};
+use time_range::TimeRange;
+use upload::{Event, Uploader};
+
+const BASELINE_DRIFT_TOLERANCE_PERCENT: f64 = 20.0;
 
 #[derive(Deserialize)]
 pub struct ReportEvent {}
 
-#[derive(Serialize)]
-pub struct ReportHandlerOutput {
-    message: String,
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
     lambda::run(handler_fn(report_handler)).await?;
@@ -24,8 +46,109 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
 }
 
 async fn report_handler(
-    event: Value,
+    _: Value,
     _: Context,
 ) -> Result<Value, Box<dyn std::error::Error + Send + Sync + 'static>> {
-    Ok(event)
+    let region = Region::default();
+    let ec2_client = Ec2InstanceClient::new_with_client(Ec2Client::new(region.clone()));
+    let cloud_watch_client = CloudWatchMetricsClient::new_with_client(CloudWatchClient::new(region));
+
+    let time_range = TimeRange::try_from(Utc::now())?;
+    let query = MetricQuery {
+        namespace: "AWS/EC2".to_string(),
+        metric_name: "CPUUtilization".to_string(),
+        dimensions: vec![],
+        period: 300,
+        extended_statistics: vec![],
+    };
+
+    let instances = ec2_client.describe_all_instances(vec![]).await?;
+    let aggregated = cloud_watch_client
+        .aggregate_metrics_for_instances(&instances, &query, &time_range)
+        .await?;
+
+    let threshold_unit = ThresholdUnit {
+        value: 80.0,
+        condition: Condition::Above,
+        statistic: Statistic::Average,
+    };
+    let anomaly_unit = AnomalyUnit {
+        confidence: 2.0,
+        seasonality: Some(Duration::days(1)),
+    };
+
+    let notificator: Box<dyn Notificator + Send + Sync> = match env::var("WEBHOOK_URL") {
+        Ok(url) => Box::new(WebhookNotificator::new(url)),
+        Err(_) => Box::new(NoopNotificator),
+    };
+    let sink = match env::var("STATSD_ADDR") {
+        Ok(addr) => Some(StatsdSink::new(addr, "metrics_notifier", vec![])?),
+        Err(_) => None,
+    };
+    let baseline_dir = env::var("BASELINE_DIR").ok();
+
+    let mut reports = Vec::with_capacity(instances.len());
+    for (instance_id, metrics) in &aggregated {
+        let mut dimensions = query.dimensions.clone();
+        dimensions.push(("InstanceId".to_string(), instance_id.clone()));
+        let per_instance_query = MetricQuery {
+            dimensions,
+            ..query.clone()
+        };
+        let datapoints = cloud_watch_client.fetch_datapoints(&per_instance_query, &time_range).await?;
+
+        if let Some(sink) = &sink {
+            sink.push(&query.metric_name, metrics)?;
+        }
+
+        let mut segments = threshold_unit.detect(&datapoints)?;
+        segments.extend(anomaly_unit.detect(&datapoints)?);
+        let breached_threshold = segments.first().map(|segment| format!("{:?}", segment.kind));
+
+        if let Some(reason) = &breached_threshold {
+            let ctx = NotificationContext {
+                metric_name: query.metric_name.clone(),
+                instance_id: Some(instance_id.clone()),
+                message: format!("{} on {}", reason, instance_id),
+            };
+            notificator.notify(metrics, &ctx).await?;
+        }
+
+        if let Some(dir) = &baseline_dir {
+            let path = Path::new(dir).join(format!("{}-{}.json", query.metric_name, instance_id));
+            if let Ok(previous) = BaselineRecord::load(&path) {
+                let delta = metrics.compare_to_baseline(&previous.metrics());
+                if delta.exceeds_tolerance(BASELINE_DRIFT_TOLERANCE_PERCENT) {
+                    let ctx = NotificationContext {
+                        metric_name: query.metric_name.clone(),
+                        instance_id: Some(instance_id.clone()),
+                        message: format!("baseline drift exceeded {}% on {}", BASELINE_DRIFT_TOLERANCE_PERCENT, instance_id),
+                    };
+                    notificator.notify(metrics, &ctx).await?;
+                }
+            }
+            BaselineRecord::new(&query.metric_name, instance_id, Utc::now(), metrics).save(&path)?;
+        }
+
+        reports.push(MetricReport {
+            metric_name: query.metric_name.clone(),
+            instance_id: Some(instance_id.clone()),
+            time_range: time_range.clone(),
+            metrics: metrics.clone(),
+            breached_threshold,
+        });
+    }
+    write_to_stdout(&reports)?;
+
+    let events: Vec<Event> = aggregated
+        .iter()
+        .map(|(instance_id, metrics)| Event::new(instance_id, &query.metric_name, &time_range, metrics))
+        .collect();
+
+    let endpoint = env::var("UPLOAD_ENDPOINT")?;
+    let cache_path = env::var("UPLOAD_CACHE_PATH")
+        .unwrap_or_else(|_| "/tmp/metrics-notifier-upload-cache.json".to_string());
+    Uploader::new(endpoint, cache_path).upload(events).await?;
+
+    Ok(serde_json::to_value(&reports)?)
 }
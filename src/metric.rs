@@ -1,8 +1,21 @@
-#[derive(Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MetricQuery {
+    pub namespace: String,
+    pub metric_name: String,
+    pub dimensions: Vec<(String, String)>,
+    pub period: i64,
+    pub extended_statistics: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct AggregatedMetrics {
     pub average: f64,
     pub maximum: f64,
     pub minimum: f64,
+    pub percentiles: BTreeMap<String, f64>,
 }
 
 impl Default for AggregatedMetrics {
@@ -11,6 +24,7 @@ impl Default for AggregatedMetrics {
             average: 0.0,
             maximum: 0.0,
             minimum: 0.0,
+            percentiles: BTreeMap::new(),
         }
     }
-}
\ No newline at end of file
+}
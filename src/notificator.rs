@@ -0,0 +1,149 @@
+use crate::error::MetricsNotifierError;
+use crate::metric::AggregatedMetrics;
+use async_trait::async_trait;
+
+pub struct NotificationContext {
+    pub metric_name: String,
+    pub instance_id: Option<String>,
+    pub message: String,
+}
+
+#[async_trait]
+pub trait Notificator {
+    async fn notify(&self, metrics: &AggregatedMetrics, ctx: &NotificationContext) -> Result<(), MetricsNotifierError>;
+}
+
+pub struct NoopNotificator;
+
+#[async_trait]
+impl Notificator for NoopNotificator {
+    async fn notify(&self, _metrics: &AggregatedMetrics, _ctx: &NotificationContext) -> Result<(), MetricsNotifierError> {
+        Ok(())
+    }
+}
+
+pub struct DesktopNotificator;
+
+#[async_trait]
+impl Notificator for DesktopNotificator {
+    async fn notify(&self, metrics: &AggregatedMetrics, ctx: &NotificationContext) -> Result<(), MetricsNotifierError> {
+        notify_rust::Notification::new()
+            .summary(&ctx.metric_name)
+            .body(&format!("{} (average {:.2})", ctx.message, metrics.average))
+            .show()
+            .map_err(|error| MetricsNotifierError::NotifyError(error.to_string()))?;
+        Ok(())
+    }
+}
+
+pub struct WebhookNotificator {
+    http: reqwest::Client,
+    endpoint: String,
+}
+
+impl WebhookNotificator {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        WebhookNotificator {
+            http: reqwest::Client::new(),
+            endpoint: endpoint.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notificator for WebhookNotificator {
+    async fn notify(&self, metrics: &AggregatedMetrics, ctx: &NotificationContext) -> Result<(), MetricsNotifierError> {
+        let payload = serde_json::json!({
+            "text": ctx.message,
+            "metric_name": ctx.metric_name,
+            "instance_id": ctx.instance_id,
+            "average": metrics.average,
+            "maximum": metrics.maximum,
+            "minimum": metrics.minimum,
+        });
+
+        self.http
+            .post(&self.endpoint)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|error| MetricsNotifierError::NotifyError(error.to_string()))?
+            .error_for_status()
+            .map_err(|error| MetricsNotifierError::NotifyError(error.to_string()))?;
+        Ok(())
+    }
+}
+
+pub struct CompositeNotificator {
+    backends: Vec<Box<dyn Notificator + Send + Sync>>,
+}
+
+impl CompositeNotificator {
+    pub fn new(backends: Vec<Box<dyn Notificator + Send + Sync>>) -> Self {
+        CompositeNotificator { backends }
+    }
+}
+
+#[async_trait]
+impl Notificator for CompositeNotificator {
+    async fn notify(&self, metrics: &AggregatedMetrics, ctx: &NotificationContext) -> Result<(), MetricsNotifierError> {
+        let mut failures = Vec::new();
+        for backend in &self.backends {
+            if let Err(error) = backend.notify(metrics, ctx).await {
+                failures.push(error.to_string());
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(MetricsNotifierError::NotifyError(failures.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingNotificator;
+
+    #[async_trait]
+    impl Notificator for FailingNotificator {
+        async fn notify(&self, _metrics: &AggregatedMetrics, _ctx: &NotificationContext) -> Result<(), MetricsNotifierError> {
+            Err(MetricsNotifierError::NotifyError("boom".to_string()))
+        }
+    }
+
+    fn context() -> NotificationContext {
+        NotificationContext {
+            metric_name: "CPUUtilization".to_string(),
+            instance_id: Some("i-1234567890abcdef0".to_string()),
+            message: "threshold breached".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_noop_notificator_always_succeeds() {
+        let notificator = NoopNotificator;
+        let result = notificator.notify(&AggregatedMetrics::default(), &context()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_composite_notificator_succeeds_when_all_backends_succeed() {
+        let composite = CompositeNotificator::new(vec![Box::new(NoopNotificator), Box::new(NoopNotificator)]);
+        let result = composite.notify(&AggregatedMetrics::default(), &context()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_composite_notificator_aggregates_failures() {
+        let composite = CompositeNotificator::new(vec![Box::new(NoopNotificator), Box::new(FailingNotificator)]);
+        let result = composite.notify(&AggregatedMetrics::default(), &context()).await;
+        assert_eq!(
+            result.err().unwrap(),
+            MetricsNotifierError::NotifyError("boom".to_string())
+        );
+    }
+}
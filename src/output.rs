@@ -0,0 +1,64 @@
+use crate::error::MetricsNotifierError;
+use crate::metric::AggregatedMetrics;
+use crate::time_range::TimeRange;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct MetricReport {
+    pub metric_name: String,
+    pub instance_id: Option<String>,
+    pub time_range: TimeRange,
+    pub metrics: AggregatedMetrics,
+    pub breached_threshold: Option<String>,
+}
+
+pub fn write_to_stdout(reports: &[MetricReport]) -> Result<(), MetricsNotifierError> {
+    let json = serde_json::to_string(reports)
+        .map_err(|error| MetricsNotifierError::SerializationError(error.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+pub fn write_to_file(reports: &[MetricReport], path: impl AsRef<Path>) -> Result<(), MetricsNotifierError> {
+    let json = serde_json::to_string_pretty(reports)
+        .map_err(|error| MetricsNotifierError::SerializationError(error.to_string()))?;
+    fs::write(path, json).map_err(|error| MetricsNotifierError::UploadError(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+    use chrono::{DateTime, Utc};
+
+    fn sample_report() -> MetricReport {
+        let time_range = TimeRange::try_from(
+            DateTime::<Utc>::from_str("2020-12-01T15:00:00.0+00:00").unwrap(),
+        )
+        .unwrap();
+
+        MetricReport {
+            metric_name: "CPUUtilization".to_string(),
+            instance_id: Some("i-1234567890abcdef0".to_string()),
+            time_range,
+            metrics: AggregatedMetrics::default(),
+            breached_threshold: Some("Above(80)".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_write_to_file_round_trips_as_json() {
+        let path = std::env::temp_dir().join(format!("metrics-notifier-output-test-{}.json", std::process::id()));
+        write_to_file(&[sample_report()], &path).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        let value: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(value[0]["metric_name"], "CPUUtilization");
+        assert_eq!(value[0]["instance_id"], "i-1234567890abcdef0");
+    }
+}
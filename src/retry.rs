@@ -0,0 +1,64 @@
+use crate::error::MetricsNotifierError;
+use std::future::Future;
+use std::time::Duration;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const BASE_BACKOFF: Duration = Duration::from_millis(100);
+
+pub async fn with_backoff<T, F, Fut>(operation: F) -> Result<T, MetricsNotifierError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, MetricsNotifierError>>,
+{
+    with_backoff_attempts(DEFAULT_MAX_ATTEMPTS, operation).await
+}
+
+pub async fn with_backoff_attempts<T, F, Fut>(max_attempts: u32, operation: F) -> Result<T, MetricsNotifierError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, MetricsNotifierError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt + 1 < max_attempts && error.is_retryable() => {
+                tokio::time::sleep(BASE_BACKOFF * 2u32.pow(attempt)).await;
+                attempt += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_with_backoff_returns_first_success() {
+        let attempts = AtomicU32::new(0);
+        let result: Result<u32, MetricsNotifierError> = with_backoff(|| async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Ok(42)
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_with_backoff_gives_up_on_non_retryable_error() {
+        let attempts = AtomicU32::new(0);
+        let result: Result<u32, MetricsNotifierError> = with_backoff(|| async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(MetricsNotifierError::NoneValue)
+        })
+        .await;
+
+        assert_eq!(result.err().unwrap(), MetricsNotifierError::NoneValue);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error::MetricsNotifierError;
+use crate::metric::AggregatedMetrics;
+use std::net::UdpSocket;
+
+pub trait MetricsSink {
+    fn push(&self, metric_name: &str, metrics: &AggregatedMetrics) -> Result<(), MetricsNotifierError>;
+}
+
+pub struct StatsdSink {
+    socket: UdpSocket,
+    server_addr: String,
+    prefix: String,
+    tags: Vec<(String, String)>,
+}
+
+impl StatsdSink {
+    pub fn new(
+        server_addr: impl Into<String>,
+        prefix: impl Into<String>,
+        tags: Vec<(String, String)>,
+    ) -> Result<Self, MetricsNotifierError> {
+        let socket = UdpSocket::bind("0.0.0.0:0").map_err(|error| MetricsNotifierError::UploadError(error.to_string()))?;
+        Ok(StatsdSink {
+            socket,
+            server_addr: server_addr.into(),
+            prefix: prefix.into(),
+            tags,
+        })
+    }
+}
+
+impl MetricsSink for StatsdSink {
+    fn push(&self, metric_name: &str, metrics: &AggregatedMetrics) -> Result<(), MetricsNotifierError> {
+        for (suffix, value) in [
+            ("average", metrics.average),
+            ("maximum", metrics.maximum),
+            ("minimum", metrics.minimum),
+        ] {
+            let line = gauge_line(&self.prefix, metric_name, suffix, value, &self.tags);
+            self.socket
+                .send_to(line.as_bytes(), &self.server_addr)
+                .map_err(|error| MetricsNotifierError::UploadError(error.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+fn gauge_line(prefix: &str, metric_name: &str, suffix: &str, value: f64, tags: &[(String, String)]) -> String {
+    format!("{}.{}.{}:{}|g{}", prefix, metric_name, suffix, value, tags_suffix(tags))
+}
+
+fn tags_suffix(tags: &[(String, String)]) -> String {
+    if tags.is_empty() {
+        return String::new();
+    }
+    let joined = tags
+        .iter()
+        .map(|(key, value)| format!("{}:{}", key, value))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("|#{}", joined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gauge_line_without_tags() {
+        let line = gauge_line("metrics_notifier", "cpu", "average", 42.0, &[]);
+        assert_eq!(line, "metrics_notifier.cpu.average:42|g");
+    }
+
+    #[test]
+    fn test_gauge_line_with_tags() {
+        let tags = vec![("instance".to_string(), "i-1234567890abcdef0".to_string())];
+        let line = gauge_line("metrics_notifier", "cpu", "maximum", 99.0, &tags);
+        assert_eq!(line, "metrics_notifier.cpu.maximum:99|g|#instance:i-1234567890abcdef0");
+    }
+}
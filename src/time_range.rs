@@ -1,8 +1,9 @@
 use std::convert::TryFrom;
 use chrono::{DateTime, Utc, FixedOffset, TimeZone, Datelike, NaiveDate};
 use crate::error::MetricsNotifierError;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TimeRange {
     pub start: chrono::DateTime<Utc>,
     pub end: chrono::DateTime<Utc>,
@@ -0,0 +1,211 @@
+use crate::error::MetricsNotifierError;
+use crate::metric::AggregatedMetrics;
+use crate::time_range::TimeRange;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+pub const CHUNK_SIZE: usize = 25;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Event {
+    pub instance_id: String,
+    pub metric_name: String,
+    pub time_range_start: DateTime<Utc>,
+    pub time_range_end: DateTime<Utc>,
+    pub average: f64,
+    pub maximum: f64,
+    pub minimum: f64,
+    pub idempotency_key: String,
+}
+
+impl Event {
+    pub fn new(instance_id: &str, metric_name: &str, time_range: &TimeRange, metrics: &AggregatedMetrics) -> Self {
+        Event {
+            instance_id: instance_id.to_string(),
+            metric_name: metric_name.to_string(),
+            time_range_start: time_range.start,
+            time_range_end: time_range.end,
+            average: metrics.average,
+            maximum: metrics.maximum,
+            minimum: metrics.minimum,
+            idempotency_key: idempotency_key(instance_id, metric_name, time_range),
+        }
+    }
+}
+
+// Derived only from the report's own identity (instance, metric, window) — not a
+// process-local counter — so a Lambda retry recomputing the same report produces the
+// same key and the receiver can actually dedupe it.
+fn idempotency_key(instance_id: &str, metric_name: &str, time_range: &TimeRange) -> String {
+    let mut hasher = DefaultHasher::new();
+    instance_id.hash(&mut hasher);
+    metric_name.hash(&mut hasher);
+    time_range.start.hash(&mut hasher);
+    time_range.end.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+pub fn chunk_events(events: Vec<Event>) -> Vec<Vec<Event>> {
+    events
+        .chunks(CHUNK_SIZE)
+        .map(|chunk| chunk.to_vec())
+        .collect()
+}
+
+pub struct Uploader {
+    http: reqwest::Client,
+    endpoint: String,
+    cache_path: PathBuf,
+}
+
+impl Uploader {
+    pub fn new(endpoint: impl Into<String>, cache_path: impl Into<PathBuf>) -> Self {
+        Uploader {
+            http: reqwest::Client::new(),
+            endpoint: endpoint.into(),
+            cache_path: cache_path.into(),
+        }
+    }
+
+    pub async fn upload(&self, events: Vec<Event>) -> Result<(), MetricsNotifierError> {
+        self.replay_cached().await?;
+        for chunk in chunk_events(events) {
+            self.upload_chunk(&chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn upload_chunk(&self, chunk: &[Event]) -> Result<(), MetricsNotifierError> {
+        self.persist(chunk)?;
+        self.send(chunk).await?;
+        self.evict(chunk)
+    }
+
+    async fn replay_cached(&self) -> Result<(), MetricsNotifierError> {
+        for chunk in self.cached_chunks()? {
+            self.send(&chunk).await?;
+            self.evict(&chunk)?;
+        }
+        Ok(())
+    }
+
+    async fn send(&self, chunk: &[Event]) -> Result<(), MetricsNotifierError> {
+        self.http
+            .post(&self.endpoint)
+            .json(chunk)
+            .send()
+            .await
+            .map_err(|error| MetricsNotifierError::UploadError(error.to_string()))?
+            .error_for_status()
+            .map_err(|error| MetricsNotifierError::UploadError(error.to_string()))?;
+        Ok(())
+    }
+
+    fn cached_chunks(&self) -> Result<Vec<Vec<Event>>, MetricsNotifierError> {
+        if !self.cache_path.exists() {
+            return Ok(vec![]);
+        }
+        let contents = fs::read_to_string(&self.cache_path)
+            .map_err(|error| MetricsNotifierError::UploadError(error.to_string()))?;
+        contents
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .map_err(|error| MetricsNotifierError::SerializationError(error.to_string()))
+            })
+            .collect()
+    }
+
+    fn persist(&self, chunk: &[Event]) -> Result<(), MetricsNotifierError> {
+        use std::io::Write;
+
+        let line = serde_json::to_string(chunk)
+            .map_err(|error| MetricsNotifierError::SerializationError(error.to_string()))?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.cache_path)
+            .map_err(|error| MetricsNotifierError::UploadError(error.to_string()))?;
+        writeln!(file, "{}", line).map_err(|error| MetricsNotifierError::UploadError(error.to_string()))
+    }
+
+    fn evict(&self, chunk: &[Event]) -> Result<(), MetricsNotifierError> {
+        let keys: Vec<&String> = chunk.iter().map(|event| &event.idempotency_key).collect();
+        let remaining: Vec<Vec<Event>> = self
+            .cached_chunks()?
+            .into_iter()
+            .filter(|cached| {
+                !cached
+                    .iter()
+                    .all(|event| keys.contains(&&event.idempotency_key))
+            })
+            .collect();
+
+        let contents = remaining
+            .iter()
+            .map(|cached| {
+                serde_json::to_string(cached)
+                    .map_err(|error| MetricsNotifierError::SerializationError(error.to_string()))
+            })
+            .collect::<Result<Vec<String>, MetricsNotifierError>>()?
+            .join("\n");
+
+        fs::write(&self.cache_path, contents)
+            .map_err(|error| MetricsNotifierError::UploadError(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    fn sample_time_range() -> TimeRange {
+        TimeRange::try_from(DateTime::<Utc>::from_str("2020-12-01T15:00:00.0+00:00").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_idempotency_key_is_stable_across_retries() {
+        let time_range = sample_time_range();
+        let a = idempotency_key("i-1", "CPUUtilization", &time_range);
+        let b = idempotency_key("i-1", "CPUUtilization", &time_range);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_idempotency_key_differs_per_report_identity() {
+        let time_range = sample_time_range();
+        let a = idempotency_key("i-1", "CPUUtilization", &time_range);
+        let b = idempotency_key("i-2", "CPUUtilization", &time_range);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_event_new_is_idempotent_across_repeated_calls() {
+        let time_range = sample_time_range();
+        let metrics = AggregatedMetrics::default();
+        let a = Event::new("i-1", "CPUUtilization", &time_range, &metrics);
+        let b = Event::new("i-1", "CPUUtilization", &time_range, &metrics);
+        assert_eq!(a.idempotency_key, b.idempotency_key);
+    }
+
+    #[test]
+    fn test_chunk_events_respects_chunk_size() {
+        let time_range = sample_time_range();
+        let metrics = AggregatedMetrics::default();
+        let events: Vec<Event> = (0..CHUNK_SIZE + 1)
+            .map(|_| Event::new("i-1", "CPUUtilization", &time_range, &metrics))
+            .collect();
+
+        let chunks = chunk_events(events);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), CHUNK_SIZE);
+        assert_eq!(chunks[1].len(), 1);
+    }
+}